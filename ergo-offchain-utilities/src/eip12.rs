@@ -0,0 +1,515 @@
+/// This file holds conversions between ergo-lib types and the EIP-12 JSON format used by
+/// browser wallets implementing the dApp connector (e.g. Nautilus/Yoroi).
+/// https://github.com/ergoplatform/eips/pull/23
+use crate::encoding::{decode_hex, EncodingError, Result};
+use ergo_lib::ast::constant::Constant;
+use ergo_lib::chain::ergo_box::{
+    BoxValue, ErgoBox, ErgoBoxCandidate, NonMandatoryRegisterId, NonMandatoryRegisters,
+};
+use ergo_lib::chain::token::{Token, TokenAmount, TokenId};
+use ergo_lib::chain::transaction::unsigned::UnsignedTransaction;
+use ergo_lib::chain::transaction::{DataInput, TxId, UnsignedInput};
+use ergo_lib::chain::{Base16DecodedBytes, Base16EncodedBytes, Digest32};
+use ergo_lib::ergo_tree::ErgoTree;
+use ergo_lib::serialization::SigmaSerializable;
+use ergo_lib::sigma_protocol::prover::ContextExtension;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+
+/// A token held in a box, with the amount encoded as a String as EIP-12 requires.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Eip12Asset {
+    #[serde(rename = "tokenId")]
+    pub token_id: String,
+    #[serde(deserialize_with = "deserialize_number_string")]
+    pub amount: String,
+}
+
+/// An output box candidate of an EIP-12 unsigned transaction.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Eip12BoxCandidate {
+    #[serde(deserialize_with = "deserialize_number_string")]
+    pub value: String,
+    #[serde(rename = "ergoTree")]
+    pub ergo_tree: String,
+    pub assets: Vec<Eip12Asset>,
+    #[serde(rename = "additionalRegisters")]
+    pub additional_registers: BTreeMap<String, String>,
+    #[serde(rename = "creationHeight")]
+    pub creation_height: u32,
+}
+
+/// A box already on-chain (or in the mempool) in EIP-12 format.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Eip12Box {
+    #[serde(rename = "boxId")]
+    pub box_id: String,
+    #[serde(rename = "transactionId")]
+    pub transaction_id: String,
+    pub index: u16,
+    #[serde(deserialize_with = "deserialize_number_string")]
+    pub value: String,
+    #[serde(rename = "ergoTree")]
+    pub ergo_tree: String,
+    pub assets: Vec<Eip12Asset>,
+    #[serde(rename = "additionalRegisters")]
+    pub additional_registers: BTreeMap<String, String>,
+    #[serde(rename = "creationHeight")]
+    pub creation_height: u32,
+}
+
+/// An input of an EIP-12 unsigned transaction: the full box being spent plus
+/// its context extension.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Eip12UnsignedInput {
+    #[serde(flatten)]
+    pub ergo_box: Eip12Box,
+    pub extension: BTreeMap<String, String>,
+}
+
+/// An unsigned transaction in EIP-12 format, ready to be handed to a browser
+/// wallet for signing.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Eip12UnsignedTransaction {
+    pub inputs: Vec<Eip12UnsignedInput>,
+    #[serde(rename = "dataInputs")]
+    pub data_inputs: Vec<Eip12Box>,
+    pub outputs: Vec<Eip12BoxCandidate>,
+}
+
+/// Deserializes a number which may be encoded either as a String (as EIP-12
+/// requires) or as a JSON number, as some wallets and sigma-rust itself do.
+fn deserialize_number_string<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumberString {
+        String(String),
+        Number(u64),
+    }
+    Ok(match NumberString::deserialize(deserializer)? {
+        NumberString::String(s) => s,
+        NumberString::Number(n) => n.to_string(),
+    })
+}
+
+/// Convert an `ErgoBox` into the EIP-12 box format
+pub fn box_to_eip12(b: &ErgoBox) -> Eip12Box {
+    Eip12Box {
+        box_id: b.box_id().into(),
+        transaction_id: b.transaction_id.0.clone().into(),
+        index: b.index,
+        value: b.value.as_u64().to_string(),
+        ergo_tree: ergo_tree_to_hex(&b.ergo_tree),
+        assets: tokens_to_eip12(&b.tokens),
+        additional_registers: registers_to_eip12(&b.additional_registers),
+        creation_height: b.creation_height,
+    }
+}
+
+/// Convert an EIP-12 box into an `ErgoBox`. Fails if any field cannot be parsed
+/// or if the provided box id does not match the box contents.
+pub fn box_from_eip12(b: &Eip12Box) -> Result<ErgoBox> {
+    let ergo_box = ErgoBox::new(
+        parse_box_value(&b.value)?,
+        parse_ergo_tree(&b.ergo_tree)?,
+        parse_tokens(&b.assets)?,
+        parse_registers(&b.additional_registers)?,
+        b.creation_height,
+        TxId(parse_digest(&b.transaction_id)?),
+        b.index,
+    );
+    let box_id: String = ergo_box.box_id().into();
+    if box_id != b.box_id {
+        return Err(EncodingError::FailedToDeserialize(b.box_id.clone()));
+    }
+    Ok(ergo_box)
+}
+
+/// Convert an `ErgoBoxCandidate` into the EIP-12 box candidate format
+pub fn box_candidate_to_eip12(b: &ErgoBoxCandidate) -> Eip12BoxCandidate {
+    Eip12BoxCandidate {
+        value: b.value.as_u64().to_string(),
+        ergo_tree: ergo_tree_to_hex(&b.ergo_tree),
+        assets: tokens_to_eip12(&b.tokens),
+        additional_registers: registers_to_eip12(&b.additional_registers),
+        creation_height: b.creation_height,
+    }
+}
+
+/// Convert an EIP-12 box candidate into an `ErgoBoxCandidate`
+pub fn box_candidate_from_eip12(b: &Eip12BoxCandidate) -> Result<ErgoBoxCandidate> {
+    Ok(ErgoBoxCandidate {
+        value: parse_box_value(&b.value)?,
+        ergo_tree: parse_ergo_tree(&b.ergo_tree)?,
+        tokens: parse_tokens(&b.assets)?,
+        additional_registers: parse_registers(&b.additional_registers)?,
+        creation_height: b.creation_height,
+    })
+}
+
+/// Convert an `UnsignedTransaction` into EIP-12 format. EIP-12 requires the full
+/// boxes of the inputs and data-inputs, so every box referenced by the transaction
+/// must be present in `input_boxes`/`data_input_boxes`, otherwise a
+/// `MissingInputBox`/`MissingDataInputBox` error naming the box id is returned.
+pub fn unsigned_tx_to_eip12(
+    tx: &UnsignedTransaction,
+    input_boxes: &[ErgoBox],
+    data_input_boxes: &[ErgoBox],
+) -> Result<Eip12UnsignedTransaction> {
+    let inputs = tx
+        .inputs
+        .iter()
+        .map(|i| {
+            let box_id: String = i.box_id.clone().into();
+            let b = find_box(&box_id, input_boxes)
+                .ok_or(EncodingError::MissingInputBox(box_id.clone()))?;
+            let extension: HashMap<String, Constant> = i.extension.clone().into();
+            Ok(Eip12UnsignedInput {
+                ergo_box: box_to_eip12(b),
+                extension: extension
+                    .into_iter()
+                    .map(|(k, c)| (k, c.base16_str()))
+                    .collect(),
+            })
+        })
+        .collect::<Result<Vec<Eip12UnsignedInput>>>()?;
+    let data_inputs = tx
+        .data_inputs
+        .iter()
+        .map(|d| {
+            let box_id: String = d.box_id.clone().into();
+            find_box(&box_id, data_input_boxes)
+                .map(box_to_eip12)
+                .ok_or(EncodingError::MissingDataInputBox(box_id.clone()))
+        })
+        .collect::<Result<Vec<Eip12Box>>>()?;
+    Ok(Eip12UnsignedTransaction {
+        inputs,
+        data_inputs,
+        outputs: tx
+            .output_candidates
+            .iter()
+            .map(box_candidate_to_eip12)
+            .collect(),
+    })
+}
+
+/// Convert an EIP-12 unsigned transaction into an `UnsignedTransaction`
+pub fn unsigned_tx_from_eip12(tx: &Eip12UnsignedTransaction) -> Result<UnsignedTransaction> {
+    let inputs = tx
+        .inputs
+        .iter()
+        .map(|i| {
+            let ergo_box = box_from_eip12(&i.ergo_box)?;
+            Ok(UnsignedInput {
+                box_id: ergo_box.box_id(),
+                extension: parse_extension(&i.extension)?,
+            })
+        })
+        .collect::<Result<Vec<UnsignedInput>>>()?;
+    let data_inputs = tx
+        .data_inputs
+        .iter()
+        .map(|d| {
+            Ok(DataInput {
+                box_id: box_from_eip12(d)?.box_id(),
+            })
+        })
+        .collect::<Result<Vec<DataInput>>>()?;
+    let outputs = tx
+        .outputs
+        .iter()
+        .map(box_candidate_from_eip12)
+        .collect::<Result<Vec<ErgoBoxCandidate>>>()?;
+    Ok(UnsignedTransaction::new(inputs, data_inputs, outputs))
+}
+
+/// Find the box with the given hex-encoded box id
+fn find_box<'a>(box_id: &str, boxes: &'a [ErgoBox]) -> Option<&'a ErgoBox> {
+    boxes.iter().find(|b| {
+        let id: String = b.box_id().into();
+        id == box_id
+    })
+}
+
+fn ergo_tree_to_hex(ergo_tree: &ErgoTree) -> String {
+    Base16EncodedBytes::new(&ergo_tree.sigma_serialize_bytes()).into()
+}
+
+fn tokens_to_eip12(tokens: &[Token]) -> Vec<Eip12Asset> {
+    tokens
+        .iter()
+        .map(|t| Eip12Asset {
+            token_id: t.token_id.0.clone().into(),
+            amount: u64::from(t.amount).to_string(),
+        })
+        .collect()
+}
+
+fn registers_to_eip12(registers: &NonMandatoryRegisters) -> BTreeMap<String, String> {
+    let registers: HashMap<NonMandatoryRegisterId, Constant> = registers.clone().into();
+    registers
+        .into_iter()
+        .map(|(id, c)| (id.into(), c.base16_str()))
+        .collect()
+}
+
+fn parse_box_value(s: &str) -> Result<BoxValue> {
+    s.parse::<u64>()
+        .ok()
+        .and_then(|v| BoxValue::new(v).ok())
+        .ok_or_else(|| EncodingError::FailedToDeserialize(s.to_string()))
+}
+
+fn parse_ergo_tree(s: &str) -> Result<ErgoTree> {
+    ErgoTree::sigma_parse_bytes(decode_hex(s)?)
+        .map_err(|_| EncodingError::FailedToDeserialize(s.to_string()))
+}
+
+fn parse_digest(s: &str) -> Result<Digest32> {
+    Base16DecodedBytes::try_from(s)
+        .ok()
+        .and_then(|b| Digest32::try_from(b).ok())
+        .ok_or_else(|| EncodingError::FailedToDeserialize(s.to_string()))
+}
+
+fn parse_constant(s: &str) -> Result<Constant> {
+    Constant::sigma_parse_bytes(decode_hex(s)?)
+        .map_err(|_| EncodingError::FailedToDeserialize(s.to_string()))
+}
+
+fn parse_tokens(assets: &[Eip12Asset]) -> Result<Vec<Token>> {
+    assets
+        .iter()
+        .map(|a| {
+            let amount = a
+                .amount
+                .parse::<u64>()
+                .ok()
+                .and_then(|v| TokenAmount::try_from(v).ok())
+                .ok_or_else(|| EncodingError::FailedToDeserialize(a.amount.clone()))?;
+            Ok(Token {
+                token_id: TokenId(parse_digest(&a.token_id)?),
+                amount,
+            })
+        })
+        .collect()
+}
+
+fn parse_registers(registers: &BTreeMap<String, String>) -> Result<NonMandatoryRegisters> {
+    let mut parsed = HashMap::new();
+    for (id, value) in registers {
+        let reg_id = NonMandatoryRegisterId::try_from(id.clone())
+            .map_err(|_| EncodingError::FailedToDeserialize(id.clone()))?;
+        parsed.insert(reg_id, parse_constant(value)?);
+    }
+    NonMandatoryRegisters::try_from(parsed)
+        .map_err(|_| EncodingError::FailedToDeserialize(format!("{:?}", registers)))
+}
+
+fn parse_extension(extension: &BTreeMap<String, String>) -> Result<ContextExtension> {
+    let mut parsed = HashMap::new();
+    for (key, value) in extension {
+        parsed.insert(key.clone(), parse_constant(value)?);
+    }
+    ContextExtension::try_from(parsed)
+        .map_err(|_| EncodingError::FailedToDeserialize(format!("{:?}", extension)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::address_string_to_ergo_tree;
+    use ergo_lib::constants::MINERS_FEE_MAINNET_ADDRESS;
+
+    fn test_box() -> ErgoBox {
+        let ergo_tree =
+            address_string_to_ergo_tree(&MINERS_FEE_MAINNET_ADDRESS.to_string()).unwrap();
+        let mut registers = HashMap::new();
+        registers.insert(NonMandatoryRegisterId::R4, Constant::from(25_i64));
+        let token = Token {
+            token_id: TokenId(Digest32::zero()),
+            amount: TokenAmount::try_from(100).unwrap(),
+        };
+        ErgoBox::new(
+            BoxValue::new(1000000).unwrap(),
+            ergo_tree,
+            vec![token],
+            NonMandatoryRegisters::try_from(registers).unwrap(),
+            100,
+            TxId::zero(),
+            0,
+        )
+    }
+
+    #[test]
+    fn eip12_roundtrip_is_valid() {
+        let b = test_box();
+        let eip12_box = box_to_eip12(&b);
+        assert_eq!(eip12_box.value, "1000000");
+        assert_eq!(eip12_box.assets[0].amount, "100");
+        assert_eq!(box_from_eip12(&eip12_box).unwrap(), b);
+
+        let candidate = ErgoBoxCandidate {
+            value: b.value,
+            ergo_tree: b.ergo_tree.clone(),
+            tokens: vec![],
+            additional_registers: NonMandatoryRegisters::empty(),
+            creation_height: 101,
+        };
+        let tx = UnsignedTransaction::new(vec![b.clone().into()], vec![], vec![candidate]);
+        let eip12_tx = unsigned_tx_to_eip12(&tx, &[b], &[]).unwrap();
+        assert_eq!(unsigned_tx_from_eip12(&eip12_tx).unwrap(), tx);
+    }
+
+    #[test]
+    fn eip12_json_shape_is_valid() {
+        let b = test_box();
+        let candidate = ErgoBoxCandidate {
+            value: b.value,
+            ergo_tree: b.ergo_tree.clone(),
+            tokens: vec![],
+            additional_registers: NonMandatoryRegisters::empty(),
+            creation_height: 101,
+        };
+        let tx = UnsignedTransaction::new(
+            vec![b.clone().into()],
+            vec![DataInput { box_id: b.box_id() }],
+            vec![candidate],
+        );
+        let boxes = vec![b.clone()];
+        let eip12_tx = unsigned_tx_to_eip12(&tx, &boxes, &boxes).unwrap();
+        let json = serde_json::to_value(&eip12_tx).unwrap();
+
+        // The input box fields are flattened next to its extension
+        let input = &json["inputs"][0];
+        let box_id: String = b.box_id().into();
+        assert_eq!(input["boxId"], box_id.as_str());
+        assert_eq!(input["transactionId"], "0".repeat(64).as_str());
+        assert_eq!(input["index"], 0);
+        assert_eq!(input["value"], "1000000");
+        assert!(input["ergoTree"].is_string());
+        assert_eq!(input["assets"][0]["amount"], "100");
+        assert!(input["assets"][0]["tokenId"].is_string());
+        assert!(input["additionalRegisters"]["R4"].is_string());
+        assert_eq!(input["creationHeight"], 100);
+        assert!(input["extension"].as_object().unwrap().is_empty());
+        assert!(input.get("ergo_box").is_none());
+
+        assert_eq!(json["dataInputs"][0]["boxId"], box_id.as_str());
+        let output = &json["outputs"][0];
+        assert_eq!(output["value"], "1000000");
+        assert_eq!(output["creationHeight"], 101);
+        assert!(output.get("boxId").is_none());
+    }
+
+    #[test]
+    fn eip12_json_payload_is_parsed() {
+        // A box taken from mainnet, as a wallet such as Nautilus would pass it
+        let json = r#"
+        {
+          "inputs": [
+            {
+              "boxId": "dd4e69ae683d7c2d1de2b3174182e6c443fd68abbcc24002ddc99adb599e0193",
+              "transactionId": "8204d2bbaabf946f89a27b366d1356eb10241dc1619a70b4e4a4a38b520926ce",
+              "index": 0,
+              "value": "1000000",
+              "ergoTree": "0008cd03f1102eb87a4166bf9fbd6247d087e92e1412b0e819dbb5fbc4e716091ec4e4ec",
+              "assets": [],
+              "additionalRegisters": {},
+              "creationHeight": 268539,
+              "extension": {}
+            }
+          ],
+          "dataInputs": [],
+          "outputs": [
+            {
+              "value": "1000000",
+              "ergoTree": "0008cd03f1102eb87a4166bf9fbd6247d087e92e1412b0e819dbb5fbc4e716091ec4e4ec",
+              "assets": [],
+              "additionalRegisters": {},
+              "creationHeight": 268540
+            }
+          ]
+        }
+        "#;
+        let eip12_tx: Eip12UnsignedTransaction = serde_json::from_str(json).unwrap();
+        let tx = unsigned_tx_from_eip12(&eip12_tx).unwrap();
+        let box_id: String = tx.inputs[0].box_id.clone().into();
+        assert_eq!(
+            box_id,
+            "dd4e69ae683d7c2d1de2b3174182e6c443fd68abbcc24002ddc99adb599e0193"
+        );
+        assert_eq!(*tx.output_candidates[0].value.as_u64(), 1000000);
+        assert_eq!(tx.output_candidates[0].creation_height, 268540);
+
+        // The same box with `value` and `amount` encoded as JSON numbers
+        let numeric_json = r#"
+        {
+          "inputs": [
+            {
+              "boxId": "dd4e69ae683d7c2d1de2b3174182e6c443fd68abbcc24002ddc99adb599e0193",
+              "transactionId": "8204d2bbaabf946f89a27b366d1356eb10241dc1619a70b4e4a4a38b520926ce",
+              "index": 0,
+              "value": 1000000,
+              "ergoTree": "0008cd03f1102eb87a4166bf9fbd6247d087e92e1412b0e819dbb5fbc4e716091ec4e4ec",
+              "assets": [],
+              "additionalRegisters": {},
+              "creationHeight": 268539,
+              "extension": {}
+            }
+          ],
+          "dataInputs": [],
+          "outputs": [
+            {
+              "value": 1000000,
+              "ergoTree": "0008cd03f1102eb87a4166bf9fbd6247d087e92e1412b0e819dbb5fbc4e716091ec4e4ec",
+              "assets": [
+                {
+                  "tokenId": "dd4e69ae683d7c2d1de2b3174182e6c443fd68abbcc24002ddc99adb599e0193",
+                  "amount": 1000
+                }
+              ],
+              "additionalRegisters": {},
+              "creationHeight": 268540
+            }
+          ]
+        }
+        "#;
+        let numeric_eip12_tx: Eip12UnsignedTransaction =
+            serde_json::from_str(numeric_json).unwrap();
+        assert_eq!(numeric_eip12_tx.inputs[0].ergo_box.value, "1000000");
+        assert_eq!(numeric_eip12_tx.outputs[0].assets[0].amount, "1000");
+        let numeric_tx = unsigned_tx_from_eip12(&numeric_eip12_tx).unwrap();
+        assert_eq!(numeric_tx.inputs, tx.inputs);
+        assert_eq!(
+            u64::from(numeric_tx.output_candidates[0].tokens[0].amount),
+            1000
+        );
+        // Serializing always produces the String encoding
+        let reserialized = serde_json::to_value(&numeric_eip12_tx).unwrap();
+        assert_eq!(reserialized["outputs"][0]["value"], "1000000");
+        assert_eq!(reserialized["outputs"][0]["assets"][0]["amount"], "1000");
+    }
+
+    #[test]
+    fn eip12_errors_are_valid() {
+        let b = test_box();
+        let box_id: String = b.box_id().into();
+        let tx = UnsignedTransaction::new(vec![b.clone().into()], vec![], vec![]);
+        match unsigned_tx_to_eip12(&tx, &[], &[]) {
+            Err(EncodingError::MissingInputBox(id)) => assert_eq!(id, box_id),
+            other => panic!("Expected MissingInputBox, got {:?}", other),
+        }
+
+        let mut tampered = box_to_eip12(&b);
+        tampered.box_id = "0".repeat(64);
+        match box_from_eip12(&tampered) {
+            Err(EncodingError::FailedToDeserialize(id)) => assert_eq!(id, tampered.box_id),
+            other => panic!("Expected FailedToDeserialize, got {:?}", other),
+        }
+    }
+}
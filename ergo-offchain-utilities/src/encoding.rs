@@ -1,6 +1,6 @@
 /// This file holds various functions related to encoding/serialization of values that are relevant
 /// to the oracle core.
-use crate::{ErgoAddressString, P2PKAddressString, P2SAddressString};
+use crate::{ErgoAddressString, P2SAddressString};
 use base16;
 use blake2b_simd::Params;
 use ergo_lib::ast::constant::{Constant, TryExtractFrom};
@@ -8,6 +8,7 @@ use ergo_lib::chain::address::{Address, AddressEncoder, NetworkPrefix};
 use ergo_lib::chain::Base16EncodedBytes;
 use ergo_lib::ergo_tree::ErgoTree;
 use ergo_lib::serialization::SigmaSerializable;
+use ergo_lib::sigma_protocol::dlog_group::EcPoint;
 use std::fmt::{Debug, Display};
 use std::str;
 use thiserror::Error;
@@ -22,6 +23,10 @@ pub enum EncodingError<T: Debug + Display> {
     FailedToDeserialize(T),
     #[error("Failed to unwrap: {0}")]
     FailedToUnwrap(T),
+    #[error("Input box {0} was not provided")]
+    MissingInputBox(T),
+    #[error("Data-input box {0} was not provided")]
+    MissingDataInputBox(T),
 }
 
 /// Takes the blake2b hash of a String, then converted into/represented as hex as a String
@@ -34,23 +39,79 @@ pub fn string_to_blake2b_hash(b: String) -> Result<String> {
 
 /// Serialize a `String` value into a signed hex-encoded byte string
 /// and then convert it into a `Constant` to be used in registers.
-pub fn serialize_string(s: &String) -> Constant {
-    let b = convert_to_signed_bytes(&s.clone().into_bytes());
+pub fn serialize_string(s: &str) -> Constant {
+    let b = convert_to_signed_bytes(s.as_bytes());
     b.into()
 }
 
 /// Decodes a hex-encoded string into bytes and then serializes it into a properly formatted signed hex-encoded string and converted into a `Constant`
-pub fn serialize_hex_encoded_string(s: &String) -> Result<Constant> {
+pub fn serialize_hex_encoded_string(s: &str) -> Result<Constant> {
     let b = decode_hex(s)?;
     let constant: Constant = convert_to_signed_bytes(&b).into();
     Ok(constant)
 }
 
+/// Serialize an `i32` Int value into a `Constant` to be used in registers.
+pub fn serialize_int(i: i32) -> Constant {
+    i.into()
+}
+
+/// Serialize an `i64` Long value into a `Constant` to be used in registers.
+pub fn serialize_long(i: i64) -> Constant {
+    i.into()
+}
+
+/// Serialize raw bytes into a `Coll[Byte]` `Constant` to be used in registers.
+pub fn serialize_bytes(b: &[u8]) -> Constant {
+    b.to_vec().into()
+}
+
+/// Serialize an `EcPoint` into a `GroupElement` `Constant` to be used in registers.
+pub fn serialize_group_element(p: EcPoint) -> Constant {
+    p.into()
+}
+
+/// Serialize a `Vec<i32>` into a `Coll[Int]` `Constant` to be used in registers.
+pub fn serialize_coll_int(v: Vec<i32>) -> Constant {
+    v.into()
+}
+
+/// Serialize a `Vec<i64>` into a `Coll[Long]` `Constant` to be used in registers.
+pub fn serialize_coll_long(v: Vec<i64>) -> Constant {
+    v.into()
+}
+
+/// Serialize a `Vec<EcPoint>` into a `Coll[GroupElement]` `Constant` to be used in registers.
+pub fn serialize_coll_group_element(v: Vec<EcPoint>) -> Constant {
+    v.into()
+}
+
+/// A tuple of 2 to 4 values which can each be serialized into a `Constant`.
+/// Sealed so that non-tuple types cannot be passed to `serialize_tuple`.
+pub trait TupleConstant: Into<Constant> + sealed::Sealed {}
+
+mod sealed {
+    pub trait Sealed {}
+    impl<A, B> Sealed for (A, B) {}
+    impl<A, B, C> Sealed for (A, B, C) {}
+    impl<A, B, C, D> Sealed for (A, B, C, D) {}
+}
+
+impl<A, B> TupleConstant for (A, B) where (A, B): Into<Constant> {}
+impl<A, B, C> TupleConstant for (A, B, C) where (A, B, C): Into<Constant> {}
+impl<A, B, C, D> TupleConstant for (A, B, C, D) where (A, B, C, D): Into<Constant> {}
+
+/// Serialize a tuple of 2 to 4 values (e.g. `(i64, i32)`) into a `Constant` to be
+/// used in registers.
+pub fn serialize_tuple<T: TupleConstant>(t: T) -> Constant {
+    t.into()
+}
+
 /// Given a P2S address, extract its `ErgoTree`, serialize it into hex-encoded
 /// bytes, hash it with blake2b_256, and then prepare it to be used
 /// in a register as a Constant
 pub fn hash_and_serialize_p2s(address: &P2SAddressString) -> Result<Constant> {
-    let ergo_tree = address_string_to_ergo_tree(&address)?;
+    let ergo_tree = address_string_to_ergo_tree(address)?;
     // Convert into hex-encoded bytes
     let base16_bytes = Base16EncodedBytes::new(&ergo_tree.sigma_serialize_bytes());
     // Convert into String
@@ -119,13 +180,13 @@ pub fn deserialize_ergo_tree_constant(c: &Constant) -> Result<P2SAddressString>
 }
 
 /// Convert Vec<i8> to Vec<u8>
-fn convert_to_unsigned_bytes(bytes: &Vec<i8>) -> Vec<u8> {
-    bytes.iter().map(|x| x.clone() as u8).collect()
+fn convert_to_unsigned_bytes(bytes: &[i8]) -> Vec<u8> {
+    bytes.iter().map(|x| *x as u8).collect()
 }
 
 /// Convert Vec<u8> to Vec<i8>
-fn convert_to_signed_bytes(bytes: &Vec<u8>) -> Vec<i8> {
-    bytes.iter().map(|x| x.clone() as i8).collect()
+fn convert_to_signed_bytes(bytes: &[u8]) -> Vec<i8> {
+    bytes.iter().map(|x| *x as i8).collect()
 }
 
 /// Takes an Ergo address (either P2PK or P2S) as a Base58 String and returns
@@ -142,22 +203,18 @@ pub fn address_string_to_ergo_tree(address_str: &ErgoAddressString) -> Result<Er
 }
 
 /// Decodes a hex-encoded string into bytes
-fn decode_hex(s: &String) -> Result<Vec<u8>> {
-    if let Ok(b) = base16::decode(s) {
-        return Ok(b);
-    } else {
-        return Err(EncodingError::FailedToSerialize(s.clone()));
-    }
+pub(crate) fn decode_hex(s: &str) -> Result<Vec<u8>> {
+    base16::decode(s).map_err(|_| EncodingError::FailedToSerialize(s.to_string()))
 }
 
 /// Convert from Erg to nanoErg
 pub fn erg_to_nanoerg(erg_amount: f64) -> u64 {
-    (erg_amount * 1000000000 as f64) as u64
+    (erg_amount * 1000000000_f64) as u64
 }
 
 /// Convert from nanoErg to Erg
 pub fn nanoerg_to_erg(nanoerg_amount: u64) -> f64 {
-    (nanoerg_amount as f64) / (1000000000 as f64)
+    (nanoerg_amount as f64) / 1000000000_f64
 }
 
 #[cfg(test)]
@@ -166,14 +223,42 @@ mod tests {
 
     #[test]
     fn erg_conv_is_valid() {
-        assert_eq!((1 as f64), nanoerg_to_erg(1000000000));
-        assert_eq!((1.23 as f64), nanoerg_to_erg(1230000000));
+        assert_eq!(1_f64, nanoerg_to_erg(1000000000));
+        assert_eq!(1.23_f64, nanoerg_to_erg(1230000000));
 
-        assert_eq!(1000000000, erg_to_nanoerg(1 as f64));
+        assert_eq!(1000000000, erg_to_nanoerg(1_f64));
         assert_eq!(erg_to_nanoerg(3.64), 3640000000);
         assert_eq!(erg_to_nanoerg(0.64), 640000000);
         assert_eq!(erg_to_nanoerg(0.0064), 6400000);
         assert_eq!(erg_to_nanoerg(0.000000064), 64);
         assert_eq!(erg_to_nanoerg(0.000000001), 1);
     }
+
+    #[test]
+    fn constant_serialization_is_valid() {
+        assert_eq!(unwrap_int(&serialize_int(25)).unwrap(), 25);
+        assert_eq!(unwrap_long(&serialize_long(-3)).unwrap(), -3);
+        assert_eq!(
+            unwrap_hex_encoded_string(&serialize_bytes(&[1, 255])).unwrap(),
+            "01ff"
+        );
+        assert_eq!(
+            Vec::<i64>::try_extract_from(serialize_coll_long(vec![1, 2])).unwrap(),
+            vec![1, 2]
+        );
+        assert_eq!(
+            <(i64, i32)>::try_extract_from(serialize_tuple((5_i64, 6_i32))).unwrap(),
+            (5, 6)
+        );
+        assert_eq!(
+            <(i32, i32, i64, Vec<u8>)>::try_extract_from(serialize_tuple((
+                1_i32,
+                2_i32,
+                3_i64,
+                vec![4_u8]
+            )))
+            .unwrap(),
+            (1, 2, 3, vec![4])
+        );
+    }
 }
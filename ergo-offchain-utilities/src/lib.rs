@@ -1,5 +1,9 @@
 /// General utilities to make writing off-chain Ergo code in Rust simpler.
+pub mod eip12;
 pub mod encoding;
+pub mod local_prover;
+pub mod registers;
+pub mod tx_creation;
 
 /// A Base58 encoded String of an Ergo address. Can be either P2PK or P2S.
 pub type ErgoAddressString = String;
@@ -0,0 +1,160 @@
+/// This file holds a prover which signs transactions locally with DLog secret keys,
+/// so that no node wallet has to be exposed.
+use crate::encoding::decode_hex;
+use crate::{BlockHeight, P2PKAddressString};
+use ergo_lib::chain::address::{AddressEncoder, NetworkPrefix};
+use ergo_lib::chain::ergo_box::ErgoBox;
+use ergo_lib::chain::ergo_state_context::{ErgoStateContext, PreHeader};
+use ergo_lib::chain::transaction::unsigned::UnsignedTransaction;
+use ergo_lib::chain::transaction::Transaction;
+use ergo_lib::wallet::secret_key::SecretKey;
+use ergo_lib::wallet::signing::TransactionContext;
+use ergo_lib::wallet::Wallet;
+use std::convert::TryFrom;
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, LocalProverError>;
+
+#[derive(Error, Debug)]
+pub enum LocalProverError {
+    #[error("Invalid DLog secret key at index {index}: {reason}")]
+    InvalidSecretKey { index: usize, reason: String },
+    #[error("Invalid block height: {0}")]
+    InvalidBlockHeight(BlockHeight),
+    #[error("Failed to sign transaction: {0}")]
+    FailedToSign(String),
+}
+
+/// Signs transactions with a set of DLog secret keys held in memory.
+pub struct LocalProver {
+    wallet: Wallet,
+    addresses: Vec<P2PKAddressString>,
+}
+
+impl LocalProver {
+    /// Create a `LocalProver` from DLog secret keys
+    pub fn from_secret_keys(secrets: Vec<SecretKey>) -> LocalProver {
+        let encoder = AddressEncoder::new(NetworkPrefix::Mainnet);
+        let addresses = secrets
+            .iter()
+            .map(|s| encoder.address_to_str(&s.get_address_from_public_image()))
+            .collect();
+        LocalProver {
+            wallet: Wallet::from_secrets(secrets),
+            addresses,
+        }
+    }
+
+    /// Create a `LocalProver` from hex-encoded 32 byte DLog secret keys. An invalid
+    /// key is reported by its index in `secrets`, never by its contents.
+    pub fn from_hex_secrets(secrets: &[String]) -> Result<LocalProver> {
+        let secret_keys = secrets
+            .iter()
+            .enumerate()
+            .map(|(index, s)| {
+                let invalid = |reason: String| LocalProverError::InvalidSecretKey { index, reason };
+                let bytes = decode_hex(s).map_err(|_| invalid("not valid hex".to_string()))?;
+                let bytes = <[u8; 32]>::try_from(bytes.as_slice())
+                    .map_err(|_| invalid(format!("expected 32 bytes, got {}", bytes.len())))?;
+                SecretKey::dlog_from_bytes(&bytes)
+                    .ok_or_else(|| invalid("not a valid DLog secret".to_string()))
+            })
+            .collect::<Result<Vec<SecretKey>>>()?;
+        Ok(LocalProver::from_secret_keys(secret_keys))
+    }
+
+    /// The mainnet P2PK addresses of the secret keys held by the prover
+    pub fn addresses(&self) -> &[P2PKAddressString] {
+        &self.addresses
+    }
+
+    /// Sign `unsigned_tx`. `boxes_to_spend` and `data_boxes` must be the boxes
+    /// referenced by its inputs and data-inputs, in the same order.
+    pub fn sign_transaction(
+        &self,
+        unsigned_tx: UnsignedTransaction,
+        boxes_to_spend: Vec<ErgoBox>,
+        data_boxes: Vec<ErgoBox>,
+        current_height: BlockHeight,
+    ) -> Result<Transaction> {
+        let height = i32::try_from(current_height)
+            .map_err(|_| LocalProverError::InvalidBlockHeight(current_height))?;
+        let tx_context = TransactionContext {
+            spending_tx: unsigned_tx,
+            boxes_to_spend,
+            data_boxes,
+        };
+        let state_context = ErgoStateContext {
+            pre_header: PreHeader { height },
+        };
+        self.wallet
+            .sign_transaction(tx_context, &state_context)
+            .map_err(|e| LocalProverError::FailedToSign(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::address_string_to_ergo_tree;
+    use ergo_lib::chain::ergo_box::{BoxValue, ErgoBoxCandidate, NonMandatoryRegisters};
+    use ergo_lib::chain::transaction::TxId;
+
+    #[test]
+    fn local_signing_is_valid() {
+        let secret = SecretKey::random_dlog();
+        let hex_secret = base16::encode_lower(&secret.to_bytes());
+        let bad_secret = format!("0x{}", "11".repeat(32));
+        let hex_secrets = vec![hex_secret, bad_secret];
+        let prover = LocalProver::from_hex_secrets(&hex_secrets[..1]).unwrap();
+        assert_eq!(
+            prover.addresses(),
+            LocalProver::from_secret_keys(vec![secret]).addresses()
+        );
+
+        let ergo_tree = address_string_to_ergo_tree(&prover.addresses()[0]).unwrap();
+        let input_box = ErgoBox::new(
+            BoxValue::SAFE_USER_MIN,
+            ergo_tree.clone(),
+            vec![],
+            NonMandatoryRegisters::empty(),
+            100,
+            TxId::zero(),
+            0,
+        );
+        let output = ErgoBoxCandidate {
+            value: BoxValue::SAFE_USER_MIN,
+            ergo_tree,
+            tokens: vec![],
+            additional_registers: NonMandatoryRegisters::empty(),
+            creation_height: 101,
+        };
+        let unsigned_tx =
+            UnsignedTransaction::new(vec![input_box.clone().into()], vec![], vec![output]);
+
+        let tx = prover
+            .sign_transaction(unsigned_tx.clone(), vec![input_box.clone()], vec![], 101)
+            .unwrap();
+        assert_eq!(tx.inputs[0].box_id, input_box.box_id());
+
+        let other_prover = LocalProver::from_secret_keys(vec![SecretKey::random_dlog()]);
+        assert!(matches!(
+            other_prover.sign_transaction(unsigned_tx, vec![input_box], vec![], 101),
+            Err(LocalProverError::FailedToSign(_))
+        ));
+        let secret_error = LocalProver::from_hex_secrets(&hex_secrets).err().unwrap();
+        assert!(matches!(
+            secret_error,
+            LocalProverError::InvalidSecretKey { index: 1, .. }
+        ));
+        assert!(!secret_error.to_string().contains(&"11".repeat(32)));
+        assert!(matches!(
+            LocalProver::from_hex_secrets(&["00ff".to_string()]),
+            Err(LocalProverError::InvalidSecretKey { index: 0, .. })
+        ));
+        assert!(matches!(
+            LocalProver::from_hex_secrets(&["ff".repeat(32)]),
+            Err(LocalProverError::InvalidSecretKey { index: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+/// This file holds functions for extracting typed values out of the registers of an `ErgoBox`.
+use ergo_lib::ast::constant::{Constant, TryExtractFrom};
+use ergo_lib::chain::ergo_box::{ErgoBox, NonMandatoryRegisterId};
+use ergo_lib::sigma_protocol::dlog_group::EcPoint;
+use ergo_lib::sigma_protocol::sigma_boolean::SigmaProp;
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, RegisterError>;
+
+#[derive(Error, Debug)]
+pub enum RegisterError {
+    #[error("Register {register:?} of box {box_id} is empty.")]
+    EmptyRegister {
+        box_id: String,
+        register: NonMandatoryRegisterId,
+    },
+    #[error("Register {register:?} of box {box_id} does not hold a {expected}: {found}")]
+    InvalidRegisterType {
+        box_id: String,
+        register: NonMandatoryRegisterId,
+        expected: String,
+        found: String,
+    },
+}
+
+/// Extracts a value of type `T` from the given register of an `ErgoBox`.
+/// `type_name` is the name of the expected type used in the error if the
+/// register holds a different type.
+fn extract<T: TryExtractFrom<Constant>>(
+    b: &ErgoBox,
+    register: NonMandatoryRegisterId,
+    type_name: &str,
+) -> Result<T> {
+    let c = b
+        .additional_registers
+        .get(register)
+        .ok_or_else(|| RegisterError::EmptyRegister {
+            box_id: b.box_id().into(),
+            register,
+        })?;
+    T::try_extract_from(c.clone()).map_err(|_| RegisterError::InvalidRegisterType {
+        box_id: b.box_id().into(),
+        register,
+        expected: type_name.to_string(),
+        found: c.base16_str(),
+    })
+}
+
+/// Extracts a `Boolean` from a register
+pub fn extract_bool(b: &ErgoBox, register: NonMandatoryRegisterId) -> Result<bool> {
+    extract(b, register, "Boolean")
+}
+
+/// Extracts a `Byte` from a register
+pub fn extract_byte(b: &ErgoBox, register: NonMandatoryRegisterId) -> Result<i8> {
+    extract(b, register, "Byte")
+}
+
+/// Extracts a `Short` from a register
+pub fn extract_short(b: &ErgoBox, register: NonMandatoryRegisterId) -> Result<i16> {
+    extract(b, register, "Short")
+}
+
+/// Extracts an `Int` from a register
+pub fn extract_int(b: &ErgoBox, register: NonMandatoryRegisterId) -> Result<i32> {
+    extract(b, register, "Int")
+}
+
+/// Extracts a `Long` from a register
+pub fn extract_long(b: &ErgoBox, register: NonMandatoryRegisterId) -> Result<i64> {
+    extract(b, register, "Long")
+}
+
+/// Extracts a `Coll[Byte]` from a register
+pub fn extract_coll_byte(b: &ErgoBox, register: NonMandatoryRegisterId) -> Result<Vec<u8>> {
+    extract(b, register, "Coll[Byte]")
+}
+
+/// Extracts a `GroupElement` from a register
+pub fn extract_group_element(b: &ErgoBox, register: NonMandatoryRegisterId) -> Result<EcPoint> {
+    extract(b, register, "GroupElement")
+}
+
+/// Extracts a `SigmaProp` from a register
+pub fn extract_sigma_prop(b: &ErgoBox, register: NonMandatoryRegisterId) -> Result<SigmaProp> {
+    extract(b, register, "SigmaProp")
+}
+
+/// Extracts a tuple of 2 to 4 items (e.g. `(i64, Vec<u8>)`) from a register
+pub fn extract_tuple<T: TryExtractFrom<Constant>>(
+    b: &ErgoBox,
+    register: NonMandatoryRegisterId,
+) -> Result<T> {
+    extract(b, register, "Tuple")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::address_string_to_ergo_tree;
+    use ergo_lib::chain::ergo_box::{BoxValue, NonMandatoryRegisters};
+    use ergo_lib::chain::transaction::TxId;
+    use ergo_lib::constants::MINERS_FEE_MAINNET_ADDRESS;
+
+    #[test]
+    fn register_extraction_is_valid() {
+        let ergo_tree =
+            address_string_to_ergo_tree(&MINERS_FEE_MAINNET_ADDRESS.to_string()).unwrap();
+        let registers = NonMandatoryRegisters::from_ordered_values(vec![
+            Constant::from(25_i64),
+            Constant::from(vec![1_u8, 2, 3]),
+            (7_i32, 8_i64).into(),
+        ])
+        .unwrap();
+        let b = ErgoBox::new(
+            BoxValue::SAFE_USER_MIN,
+            ergo_tree,
+            vec![],
+            registers,
+            100,
+            TxId::zero(),
+            0,
+        );
+
+        assert_eq!(extract_long(&b, NonMandatoryRegisterId::R4).unwrap(), 25);
+        assert_eq!(
+            extract_coll_byte(&b, NonMandatoryRegisterId::R5).unwrap(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            extract_tuple::<(i32, i64)>(&b, NonMandatoryRegisterId::R6).unwrap(),
+            (7, 8)
+        );
+        assert!(matches!(
+            extract_int(&b, NonMandatoryRegisterId::R4),
+            Err(RegisterError::InvalidRegisterType { register: NonMandatoryRegisterId::R4, expected, .. })
+                if expected == "Int"
+        ));
+        assert!(matches!(
+            extract_tuple::<(i64, i64)>(&b, NonMandatoryRegisterId::R6),
+            Err(RegisterError::InvalidRegisterType { expected, .. }) if expected == "Tuple"
+        ));
+        assert!(matches!(
+            extract_long(&b, NonMandatoryRegisterId::R7),
+            Err(RegisterError::EmptyRegister {
+                register: NonMandatoryRegisterId::R7,
+                ..
+            })
+        ));
+    }
+}
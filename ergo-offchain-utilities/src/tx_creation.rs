@@ -0,0 +1,530 @@
+/// This file holds functions for creating the boxes which go into transactions, and
+/// for building complete unsigned transactions (such as token minting) from them.
+use crate::encoding::address_string_to_ergo_tree;
+use crate::{BlockHeight, ErgoAddressString, NanoErg};
+use ergo_lib::chain::address::{AddressEncoder, NetworkPrefix};
+use ergo_lib::chain::ergo_box::box_builder::ErgoBoxCandidateBuilder;
+use ergo_lib::chain::ergo_box::{
+    sum_tokens_from_boxes, sum_value, BoxId, BoxValue, ErgoBox, ErgoBoxAssetsData,
+    ErgoBoxCandidate, NonMandatoryRegisterId,
+};
+use ergo_lib::chain::token::{Token, TokenAmount, TokenId};
+use ergo_lib::chain::transaction::unsigned::UnsignedTransaction;
+use ergo_lib::chain::transaction::TxId;
+use ergo_lib::serialization::SigmaSerializable;
+use ergo_lib::wallet::box_selector::BoxSelection;
+use ergo_lib::wallet::tx_builder::{new_miner_fee_box, TxBuilder};
+use std::convert::TryFrom;
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, TxCreationError>;
+
+#[derive(Error, Debug)]
+pub enum TxCreationError {
+    #[error("Invalid box value: {0}")]
+    InvalidBoxValue(String),
+    #[error("Invalid creation height: {0}")]
+    InvalidCreationHeight(BlockHeight),
+    #[error("Invalid token amount: {0}")]
+    InvalidTokenAmount(String),
+    #[error("Failed to create box: {0}")]
+    FailedToCreateBox(String),
+    #[error("Invalid content hash length: {0} bytes (expected 32)")]
+    InvalidContentHash(usize),
+    #[error("Failed to create transaction: {0}")]
+    FailedToCreateTx(String),
+}
+
+/// Creates the miner fee output box candidate holding `fee` nanoErgs, guarded by
+/// the canonical fee contract (which is the same on mainnet and testnet).
+pub fn create_fee_box(fee: NanoErg, height: BlockHeight) -> Result<ErgoBoxCandidate> {
+    new_miner_fee_box(box_value(fee)?, creation_height(height)?)
+        .map_err(|e| TxCreationError::FailedToCreateBox(e.to_string()))
+}
+
+/// EIP-4 metadata of a token to be minted.
+#[derive(Debug, Clone)]
+pub struct TokenMetadata {
+    pub name: String,
+    pub description: String,
+    pub decimals: usize,
+}
+
+/// The EIP-4 asset types of an NFT, encoded in R7 of the minting box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NftType {
+    Picture,
+    Audio,
+    Video,
+}
+
+impl NftType {
+    /// The bytes which identify this NFT type in R7
+    pub fn type_bytes(&self) -> Vec<u8> {
+        match self {
+            NftType::Picture => vec![0x01, 0x01],
+            NftType::Audio => vec![0x01, 0x02],
+            NftType::Video => vec![0x01, 0x03],
+        }
+    }
+}
+
+/// EIP-4 metadata of an NFT to be minted.
+#[derive(Debug, Clone)]
+pub struct NftMetadata {
+    pub name: String,
+    pub description: String,
+    pub nft_type: NftType,
+    /// SHA256 hash of the NFT's content (32 bytes)
+    pub content_hash: Vec<u8>,
+    /// Link to the NFT's content
+    pub link: String,
+}
+
+/// Creates an output box candidate at `address` which mints `amount` of a new token
+/// with EIP-4 metadata in R4-R6. Per EIP-4 the token id is the box id of the first
+/// input of the minting transaction, which must be passed as `first_input_box_id`.
+pub fn create_token_mint_box(
+    first_input_box_id: &BoxId,
+    amount: u64,
+    metadata: &TokenMetadata,
+    value: NanoErg,
+    address: &ErgoAddressString,
+    height: BlockHeight,
+) -> Result<ErgoBoxCandidate> {
+    let builder =
+        token_mint_box_builder(first_input_box_id, amount, metadata, value, address, height)?;
+    builder
+        .build()
+        .map_err(|e| TxCreationError::FailedToCreateBox(e.to_string()))
+}
+
+/// Creates an output box candidate at `address` which mints an NFT (a single unit of a
+/// token with 0 decimals) with EIP-4 metadata in R4-R9. Per EIP-4 the token id is the
+/// box id of the first input of the minting transaction, which must be passed as
+/// `first_input_box_id`.
+pub fn create_nft_mint_box(
+    first_input_box_id: &BoxId,
+    metadata: &NftMetadata,
+    value: NanoErg,
+    address: &ErgoAddressString,
+    height: BlockHeight,
+) -> Result<ErgoBoxCandidate> {
+    if metadata.content_hash.len() != 32 {
+        return Err(TxCreationError::InvalidContentHash(
+            metadata.content_hash.len(),
+        ));
+    }
+    let token_metadata = TokenMetadata {
+        name: metadata.name.clone(),
+        description: metadata.description.clone(),
+        decimals: 0,
+    };
+    let mut builder = token_mint_box_builder(
+        first_input_box_id,
+        1,
+        &token_metadata,
+        value,
+        address,
+        height,
+    )?;
+    builder.set_register_value(
+        NonMandatoryRegisterId::R7,
+        metadata.nft_type.type_bytes().into(),
+    );
+    builder.set_register_value(
+        NonMandatoryRegisterId::R8,
+        metadata.content_hash.clone().into(),
+    );
+    builder.set_register_value(
+        NonMandatoryRegisterId::R9,
+        metadata.link.as_bytes().to_vec().into(),
+    );
+    builder
+        .build()
+        .map_err(|e| TxCreationError::FailedToCreateBox(e.to_string()))
+}
+
+/// The outputs of a minting transaction, other than the minted token itself.
+#[derive(Debug, Clone)]
+pub struct MintTxParams {
+    /// nanoErgs held by the minting box
+    pub value: NanoErg,
+    /// Address the minting box is created at
+    pub address: ErgoAddressString,
+    /// Miner fee of the transaction in nanoErgs
+    pub fee: NanoErg,
+    /// Address which receives the remaining nanoErgs and tokens of the inputs
+    pub change_address: ErgoAddressString,
+}
+
+/// Creates an unsigned transaction which spends all of `inputs` and mints `amount`
+/// of a new token with EIP-4 metadata into a box at `params.address`. The token id
+/// is taken from `inputs[0]`, so the inputs must be kept in this order when signing.
+pub fn create_token_mint_tx(
+    inputs: &[ErgoBox],
+    amount: u64,
+    metadata: &TokenMetadata,
+    params: &MintTxParams,
+    height: BlockHeight,
+) -> Result<UnsignedTransaction> {
+    let first_input = first_input(inputs)?;
+    let mint_box = create_token_mint_box(
+        &first_input.box_id(),
+        amount,
+        metadata,
+        params.value,
+        &params.address,
+        height,
+    )?;
+    create_mint_tx(inputs, mint_box, params, height)
+}
+
+/// Creates an unsigned transaction which spends all of `inputs` and mints an NFT
+/// with EIP-4 metadata into a box at `params.address`. The token id is taken from
+/// `inputs[0]`, so the inputs must be kept in this order when signing.
+pub fn create_nft_mint_tx(
+    inputs: &[ErgoBox],
+    metadata: &NftMetadata,
+    params: &MintTxParams,
+    height: BlockHeight,
+) -> Result<UnsignedTransaction> {
+    let first_input = first_input(inputs)?;
+    let mint_box = create_nft_mint_box(
+        &first_input.box_id(),
+        metadata,
+        params.value,
+        &params.address,
+        height,
+    )?;
+    create_mint_tx(inputs, mint_box, params, height)
+}
+
+fn first_input(inputs: &[ErgoBox]) -> Result<&ErgoBox> {
+    inputs
+        .first()
+        .ok_or_else(|| TxCreationError::FailedToCreateTx("No inputs provided".to_string()))
+}
+
+/// Builds the minting transaction with the exact change of the inputs, as
+/// `TxBuilder` does not compute it itself. Leftover nanoErgs below the minimum
+/// box value are added to the fee when there are no tokens to return, as they
+/// cannot be held in a change box of their own.
+fn create_mint_tx(
+    inputs: &[ErgoBox],
+    mint_box: ErgoBoxCandidate,
+    params: &MintTxParams,
+    height: BlockHeight,
+) -> Result<UnsignedTransaction> {
+    let mut fee = params.fee;
+    let change_value = sum_value(inputs)
+        .checked_sub(*mint_box.value.as_u64())
+        .and_then(|v| v.checked_sub(fee))
+        .ok_or_else(|| {
+            TxCreationError::FailedToCreateTx("Not enough nanoErgs in the inputs".to_string())
+        })?;
+    let change_tokens: Vec<Token> = sum_tokens_from_boxes(inputs)
+        .into_iter()
+        .map(Token::from)
+        .collect();
+    let change_boxes = if change_value >= BoxValue::MIN_RAW {
+        vec![ErgoBoxAssetsData {
+            value: box_value(change_value)?,
+            tokens: change_tokens,
+        }]
+    } else if change_tokens.is_empty() {
+        fee += change_value;
+        vec![]
+    } else {
+        return Err(TxCreationError::FailedToCreateTx(format!(
+            "Change of {} nanoErgs is below the minimum box value needed to return the input tokens",
+            change_value
+        )));
+    };
+    let change_address = AddressEncoder::new(NetworkPrefix::Mainnet)
+        .parse_address_from_str(&params.change_address)
+        .map_err(|e| TxCreationError::FailedToCreateTx(e.to_string()))?;
+    let box_selection = BoxSelection {
+        boxes: inputs.to_vec(),
+        change_boxes,
+    };
+    TxBuilder::new(
+        box_selection,
+        vec![mint_box],
+        creation_height(height)?,
+        box_value(fee)?,
+        change_address,
+        BoxValue::MIN,
+    )
+    .build()
+    .map_err(|e| TxCreationError::FailedToCreateTx(e.to_string()))
+}
+
+fn token_mint_box_builder(
+    first_input_box_id: &BoxId,
+    amount: u64,
+    metadata: &TokenMetadata,
+    value: NanoErg,
+    address: &ErgoAddressString,
+    height: BlockHeight,
+) -> Result<ErgoBoxCandidateBuilder> {
+    let ergo_tree = address_string_to_ergo_tree(address)
+        .map_err(|e| TxCreationError::FailedToCreateBox(e.to_string()))?;
+    let token = Token {
+        token_id: TokenId::from(first_input_box_id.clone()),
+        amount: TokenAmount::try_from(amount)
+            .map_err(|e| TxCreationError::InvalidTokenAmount(e.to_string()))?,
+    };
+    let mut builder =
+        ErgoBoxCandidateBuilder::new(box_value(value)?, ergo_tree, creation_height(height)?);
+    builder.mint_token(
+        token,
+        metadata.name.clone(),
+        metadata.description.clone(),
+        metadata.decimals,
+    );
+    Ok(builder)
+}
+
+fn box_value(value: NanoErg) -> Result<BoxValue> {
+    BoxValue::new(value).map_err(|e| TxCreationError::InvalidBoxValue(e.to_string()))
+}
+
+fn creation_height(height: BlockHeight) -> Result<u32> {
+    u32::try_from(height).map_err(|_| TxCreationError::InvalidCreationHeight(height))
+}
+
+/// Calculates the minimum number of nanoErgs the protocol requires a box created
+/// from `candidate` to hold, based on its serialized size in bytes. The size is
+/// taken from the full box (including transaction id and index) as the node does,
+/// so `output_index` must be the position of the box in the transaction's outputs
+/// (indexes of 128 and above take an extra byte).
+pub fn min_box_value(candidate: &ErgoBoxCandidate, output_index: u16) -> NanoErg {
+    let b = ErgoBox::from_box_candidate(candidate, TxId::zero(), output_index);
+    b.sigma_serialize_bytes().len() as u64 * BoxValue::MIN_VALUE_PER_BOX_BYTE as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::unwrap_string;
+    use ergo_lib::chain::address::{AddressEncoder, NetworkPrefix};
+    use ergo_lib::chain::ergo_box::NonMandatoryRegisters;
+    use ergo_lib::constants::{MINERS_FEE_MAINNET_ADDRESS, MINERS_FEE_TESTNET_ADDRESS};
+
+    #[test]
+    fn fee_box_is_valid() {
+        let fee_box = create_fee_box(1100000, 100).unwrap();
+        let mainnet_tree =
+            address_string_to_ergo_tree(&MINERS_FEE_MAINNET_ADDRESS.to_string()).unwrap();
+        let testnet_tree = AddressEncoder::new(NetworkPrefix::Testnet)
+            .parse_address_from_str(MINERS_FEE_TESTNET_ADDRESS)
+            .unwrap()
+            .script()
+            .unwrap();
+
+        assert_eq!(*fee_box.value.as_u64(), 1100000);
+        assert_eq!(fee_box.creation_height, 100);
+        assert_eq!(fee_box.ergo_tree, mainnet_tree);
+        assert_eq!(fee_box.ergo_tree, testnet_tree);
+        assert!(create_fee_box(0, 100).is_err());
+    }
+
+    #[test]
+    fn min_box_value_is_valid() {
+        let fee_box = create_fee_box(1100000, 100).unwrap();
+        let mut large_box = fee_box.clone();
+        large_box.additional_registers =
+            NonMandatoryRegisters::from_ordered_values(vec![vec![0_u8; 1000].into()]).unwrap();
+
+        let min_value = min_box_value(&fee_box, 0);
+        assert!(min_value > BoxValue::MIN_RAW);
+        assert_eq!(min_value % BoxValue::MIN_VALUE_PER_BOX_BYTE as u64, 0);
+        assert!(min_box_value(&large_box, 0) > min_value + 1000 * 360);
+        assert_eq!(min_box_value(&fee_box, 127), min_value);
+        assert_eq!(min_box_value(&fee_box, 128), min_value + 360);
+    }
+
+    #[test]
+    fn mint_boxes_are_valid() {
+        let address = MINERS_FEE_MAINNET_ADDRESS.to_string();
+        let box_id = BoxId::zero();
+        let metadata = TokenMetadata {
+            name: "Test".to_string(),
+            description: "A test token".to_string(),
+            decimals: 2,
+        };
+        let token_box =
+            create_token_mint_box(&box_id, 1000, &metadata, 1000000, &address, 100).unwrap();
+        assert_eq!(token_box.tokens[0].token_id, TokenId::from(box_id.clone()));
+        assert_eq!(u64::from(token_box.tokens[0].amount), 1000);
+        let r6 = token_box
+            .additional_registers
+            .get(NonMandatoryRegisterId::R6)
+            .unwrap();
+        assert_eq!(unwrap_string(r6).unwrap(), "2");
+
+        let nft_metadata = NftMetadata {
+            name: "Test NFT".to_string(),
+            description: "A test NFT".to_string(),
+            nft_type: NftType::Picture,
+            content_hash: vec![0; 32],
+            link: "https://example.com/nft.png".to_string(),
+        };
+        let nft_box = create_nft_mint_box(&box_id, &nft_metadata, 1000000, &address, 100).unwrap();
+        assert_eq!(u64::from(nft_box.tokens[0].amount), 1);
+        assert_eq!(nft_box.additional_registers.len(), 6);
+        let r9 = nft_box
+            .additional_registers
+            .get(NonMandatoryRegisterId::R9)
+            .unwrap();
+        assert_eq!(unwrap_string(r9).unwrap(), nft_metadata.link);
+
+        let bad_hash_metadata = NftMetadata {
+            content_hash: vec![0; 31],
+            ..nft_metadata
+        };
+        assert!(matches!(
+            create_nft_mint_box(&box_id, &bad_hash_metadata, 1000000, &address, 100),
+            Err(TxCreationError::InvalidContentHash(31))
+        ));
+    }
+
+    #[test]
+    fn mint_txs_are_valid() {
+        let address = MINERS_FEE_MAINNET_ADDRESS.to_string();
+        let ergo_tree = address_string_to_ergo_tree(&address).unwrap();
+        let params = MintTxParams {
+            value: 1000000,
+            address: address.clone(),
+            fee: 1100000,
+            change_address: address.clone(),
+        };
+        let held_token = Token {
+            token_id: TokenId::from(BoxId::zero()),
+            amount: TokenAmount::try_from(5).unwrap(),
+        };
+        let inputs = vec![
+            ErgoBox::new(
+                BoxValue::new(5000000).unwrap(),
+                ergo_tree.clone(),
+                vec![held_token.clone()],
+                NonMandatoryRegisters::empty(),
+                100,
+                TxId::zero(),
+                0,
+            ),
+            ErgoBox::new(
+                BoxValue::new(3000000).unwrap(),
+                ergo_tree,
+                vec![],
+                NonMandatoryRegisters::empty(),
+                100,
+                TxId::zero(),
+                1,
+            ),
+        ];
+        let metadata = TokenMetadata {
+            name: "Test".to_string(),
+            description: "A test token".to_string(),
+            decimals: 2,
+        };
+        let tx = create_token_mint_tx(&inputs, 1000, &metadata, &params, 101).unwrap();
+        assert_eq!(tx.inputs[0].box_id, inputs[0].box_id());
+        assert_eq!(tx.inputs.len(), 2);
+        assert_eq!(tx.output_candidates.len(), 3);
+        let mint_box = &tx.output_candidates[0];
+        assert_eq!(
+            mint_box.tokens[0].token_id,
+            TokenId::from(inputs[0].box_id())
+        );
+        let change_box = &tx.output_candidates[1];
+        assert_eq!(*change_box.value.as_u64(), 8000000 - 1000000 - 1100000);
+        assert_eq!(change_box.tokens, vec![held_token]);
+        assert_eq!(*tx.output_candidates[2].value.as_u64(), 1100000);
+
+        let nft_metadata = NftMetadata {
+            name: "Test NFT".to_string(),
+            description: "A test NFT".to_string(),
+            nft_type: NftType::Picture,
+            content_hash: vec![0; 32],
+            link: "https://example.com/nft.png".to_string(),
+        };
+        let nft_tx = create_nft_mint_tx(&inputs, &nft_metadata, &params, 101).unwrap();
+        assert_eq!(
+            nft_tx.output_candidates[0].tokens[0].token_id,
+            TokenId::from(inputs[0].box_id())
+        );
+
+        assert!(matches!(
+            create_nft_mint_tx(&[], &nft_metadata, &params, 101),
+            Err(TxCreationError::FailedToCreateTx(_))
+        ));
+        assert!(matches!(
+            create_nft_mint_tx(
+                &inputs,
+                &nft_metadata,
+                &MintTxParams {
+                    value: 8000000,
+                    ..params.clone()
+                },
+                101
+            ),
+            Err(TxCreationError::FailedToCreateTx(_))
+        ));
+    }
+
+    #[test]
+    fn mint_tx_change_dust_is_handled() {
+        let address = MINERS_FEE_MAINNET_ADDRESS.to_string();
+        let ergo_tree = address_string_to_ergo_tree(&address).unwrap();
+        let params = MintTxParams {
+            value: 1000000,
+            address: address.clone(),
+            fee: 1100000,
+            change_address: address.clone(),
+        };
+        let nft_metadata = NftMetadata {
+            name: "Test NFT".to_string(),
+            description: "A test NFT".to_string(),
+            nft_type: NftType::Picture,
+            content_hash: vec![0; 32],
+            link: "https://example.com/nft.png".to_string(),
+        };
+        let input_box = |value: u64, tokens: Vec<Token>| {
+            ErgoBox::new(
+                BoxValue::new(value).unwrap(),
+                ergo_tree.clone(),
+                tokens,
+                NonMandatoryRegisters::empty(),
+                100,
+                TxId::zero(),
+                0,
+            )
+        };
+
+        // Dust without tokens to return is added to the fee
+        let inputs = vec![input_box(1000000 + 1100000 + 500, vec![])];
+        let tx = create_nft_mint_tx(&inputs, &nft_metadata, &params, 101).unwrap();
+        assert_eq!(tx.output_candidates.len(), 2);
+        assert_eq!(*tx.output_candidates[1].value.as_u64(), 1100500);
+
+        // Dust (or nothing) left to hold the tokens of the inputs is an error
+        let held_token = Token {
+            token_id: TokenId::from(BoxId::zero()),
+            amount: TokenAmount::try_from(5).unwrap(),
+        };
+        let inputs = vec![input_box(1000000 + 1100000 + 500, vec![held_token.clone()])];
+        match create_nft_mint_tx(&inputs, &nft_metadata, &params, 101) {
+            Err(TxCreationError::FailedToCreateTx(e)) => {
+                assert!(e.contains("Change of 500 nanoErgs is below the minimum box value"))
+            }
+            other => panic!("Expected FailedToCreateTx, got {:?}", other),
+        }
+        let inputs = vec![input_box(1000000 + 1100000, vec![held_token])];
+        assert!(matches!(
+            create_nft_mint_tx(&inputs, &nft_metadata, &params, 101),
+            Err(TxCreationError::FailedToCreateTx(_))
+        ));
+    }
+}